@@ -1,11 +1,40 @@
+use core::fmt::Write;
+
 use wdk_sys::ntddk::DbgPrint;
 
+/// Maximum length of a single debug print, including the prefix and null terminator. Longer
+/// output is truncated.
+const PRINT_BUF_LEN: usize = 512;
+
+/// Fixed size stack buffer used to format debug output without touching the pool, so printing
+/// can't fail (and take the system down) under memory pressure.
+struct PrintBuf {
+    buf: [u8; PRINT_BUF_LEN],
+    len: usize,
+}
+
+impl Write for PrintBuf {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        // always keep the last byte free for the null terminator
+        let n = s.len().min(PRINT_BUF_LEN - 1 - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+
+        Ok(())
+    }
+}
+
 #[doc(hidden)]
 pub fn _print(args: core::fmt::Arguments) {
-    let s = alloc::format!("[sanctum-driver]: {}\0", args);
+    let mut s = PrintBuf {
+        buf: [0; PRINT_BUF_LEN],
+        len: 0,
+    };
+    let _ = write!(s, "[sanctum-driver]: {}", args);
 
-    // print the string
-    unsafe { DbgPrint(s.as_ptr() as _) };
+    // print the string, passing it as an argument so any % in the message isn't treated as a
+    // format specifier
+    unsafe { DbgPrint("%s\0".as_ptr() as _, s.buf.as_ptr()) };
 }
 
 #[macro_export]
@@ -19,4 +48,4 @@ macro_rules! print {
 macro_rules! println {
     () => ($crate::print!("\n"));
     ($($arg:tt)*) => ($crate::print!("{}\n", format_args!($($arg)*)));
-}
\ No newline at end of file
+}